@@ -3,12 +3,22 @@ use std::boxed::Box;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::marker::PhantomData;
 use std::ops::Deref as _;
 use std::os::raw::c_ulong;
+#[cfg(feature = "tokio")]
+use std::os::unix::io::AsRawFd;
+#[cfg(feature = "tokio")]
+use std::os::unix::io::RawFd;
 use std::ptr::NonNull;
 use std::slice;
+#[cfg(feature = "tokio")]
+use std::sync::OnceLock;
 use std::time::Duration;
 
+#[cfg(feature = "tokio")]
+use tokio::io::unix::AsyncFd;
+
 use crate::libbpf_sys;
 use crate::util;
 use crate::Error;
@@ -120,7 +130,12 @@ impl<'a> RingBufferBuilder<'a> {
         }
 
         match ptr {
-            Some(ptr) => Ok(RingBuffer { ptr, _cbs: cbs }),
+            Some(ptr) => Ok(RingBuffer {
+                ptr,
+                _cbs: cbs,
+                #[cfg(feature = "tokio")]
+                async_fd: OnceLock::new(),
+            }),
             None => Err(Error::InvalidInput(
                 "You must add at least one ring buffer map and callback before building".into(),
             )),
@@ -146,6 +161,17 @@ pub struct RingBuffer<'a> {
     ptr: NonNull<libbpf_sys::ring_buffer>,
     #[allow(clippy::vec_box)]
     _cbs: Vec<Box<RingBufferCallback<'a>>>,
+    /// The `AsyncFd` registration backing [`poll_async`][Self::poll_async],
+    /// created lazily on first use and reused for the lifetime of this
+    /// `RingBuffer` so that repeated polling doesn't pay for an
+    /// epoll_ctl(ADD)/epoll_ctl(DEL) pair on every call.
+    ///
+    /// Holds the `AsyncFd::new` result rather than just the `AsyncFd` so
+    /// that concurrent first calls to `poll_async` can share a single
+    /// registration attempt via `OnceLock::get_or_init` instead of each
+    /// racing to create (and potentially tear down) their own.
+    #[cfg(feature = "tokio")]
+    async_fd: OnceLock<std::io::Result<AsyncFd<EpollFd>>>,
 }
 
 impl<'a> RingBuffer<'a> {
@@ -173,10 +199,79 @@ impl<'a> RingBuffer<'a> {
         util::parse_ret(ret)
     }
 
+    /// Consume from all open ring buffers, calling the registered callback
+    /// for each one, but stop after at most `max` samples have been
+    /// handled across all rings. Returns the number of samples actually
+    /// consumed.
+    ///
+    /// Unlike [`consume`][Self::consume], this bounds the work done per
+    /// call, so a busy ring cannot keep the call running indefinitely.
+    /// Rings are visited in the order they were added, so an earlier ring
+    /// may use up all of `max`; draining is not round-robin across rings.
+    pub fn consume_n(&self, max: usize) -> Result<usize> {
+        let ret = unsafe { libbpf_sys::ring_buffer__consume_n(self.ptr.as_ptr(), max as u64) };
+
+        util::parse_ret_usize(ret)
+    }
+
+    /// Get a handle to the ring at `idx`, in the order the rings were added
+    /// via [`RingBufferBuilder::add`]. Returns `None` if `idx` is out of
+    /// range.
+    pub fn ring(&self, idx: usize) -> Option<Ring<'_>> {
+        let idx = u32::try_from(idx).ok()?;
+        let ptr = unsafe { libbpf_sys::ring_buffer__ring(self.ptr.as_ptr(), idx) };
+
+        Some(Ring {
+            ptr: NonNull::new(ptr)?,
+            _marker: PhantomData,
+        })
+    }
+
     /// Get an fd that can be used to sleep until data is available
     pub fn epoll_fd(&self) -> i32 {
         unsafe { libbpf_sys::ring_buffer__epoll_fd(self.ptr.as_ptr()) }
     }
+
+    /// Asynchronously wait for [`epoll_fd`][Self::epoll_fd] to become
+    /// readable and then [`consume`][Self::consume] the ring buffers,
+    /// running the registered callbacks.
+    ///
+    /// This lets callers fold ring buffer draining into a `select!` loop or
+    /// a spawned task instead of dedicating an OS thread to
+    /// [`poll`][Self::poll]. Requires the `tokio` feature and a running
+    /// tokio reactor.
+    #[cfg(feature = "tokio")]
+    pub async fn poll_async(&self) -> Result<()> {
+        let to_err = |e: &std::io::Error| Error::System(e.raw_os_error().unwrap_or(0));
+
+        // `get_or_init` only ever runs the closure once, even under
+        // concurrent first calls, so at most one `AsyncFd` registration is
+        // ever created for this `RingBuffer`'s epoll fd.
+        let async_fd = self
+            .async_fd
+            .get_or_init(|| AsyncFd::new(EpollFd(self.epoll_fd())))
+            .as_ref()
+            .map_err(to_err)?;
+
+        let mut guard = async_fd.readable().await.map_err(|e| to_err(&e))?;
+        let result = self.consume();
+        guard.clear_ready();
+        result
+    }
+}
+
+/// A thin [`AsRawFd`] wrapper around a `ring_buffer`'s epoll fd, so it can be
+/// registered with [`AsyncFd`] without tokio taking ownership of (and
+/// closing) the fd itself.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+struct EpollFd(RawFd);
+
+#[cfg(feature = "tokio")]
+impl AsRawFd for EpollFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
 }
 
 // SAFETY: `ring_buffer` objects can safely be polled from any thread.
@@ -190,6 +285,37 @@ impl Drop for RingBuffer<'_> {
     }
 }
 
+/// A handle to a single ring registered with a [`RingBuffer`], allowing
+/// introspection of that ring's state independent of the others.
+///
+/// Borrowed from a [`RingBuffer`] via [`RingBuffer::ring`]; it is not valid
+/// to outlive the `RingBuffer` it came from.
+#[derive(Debug)]
+pub struct Ring<'a> {
+    ptr: NonNull<libbpf_sys::ring>,
+    _marker: PhantomData<&'a RingBuffer<'a>>,
+}
+
+impl Ring<'_> {
+    /// The number of bytes of unconsumed data currently available in this
+    /// ring.
+    pub fn avail_data_size(&self) -> usize {
+        unsafe { libbpf_sys::ring__avail_data_size(self.ptr.as_ptr()) as usize }
+    }
+
+    /// The current consumer position (i.e. how many bytes have been
+    /// consumed) for this ring.
+    pub fn consumer_pos(&self) -> u64 {
+        unsafe { libbpf_sys::ring__consumer_pos(self.ptr.as_ptr()) }
+    }
+
+    /// The current producer position (i.e. how many bytes have been
+    /// produced) for this ring.
+    pub fn producer_pos(&self) -> u64 {
+        unsafe { libbpf_sys::ring__producer_pos(self.ptr.as_ptr()) }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -205,4 +331,29 @@ mod test {
 
         test::<RingBuffer>();
     }
+
+    /// Exercise the same `AsyncFd<EpollFd>` readiness mechanism that
+    /// [`RingBuffer::poll_async`] relies on, using a Unix socket pair in place of an
+    /// actual `ring_buffer`'s epoll fd.
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn epoll_fd_becomes_readable() {
+        use std::io::Read;
+        use std::io::Write;
+        use std::os::unix::net::UnixStream;
+
+        let (mut tx, mut rx) = UnixStream::pair().unwrap();
+        rx.set_nonblocking(true).unwrap();
+
+        let async_fd = AsyncFd::new(EpollFd(rx.as_raw_fd())).unwrap();
+
+        tx.write_all(b"x").unwrap();
+
+        let mut guard = async_fd.readable().await.unwrap();
+        let mut buf = [0u8; 1];
+        rx.read_exact(&mut buf).unwrap();
+        guard.clear_ready();
+
+        assert_eq!(&buf, b"x");
+    }
 }
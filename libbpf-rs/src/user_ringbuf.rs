@@ -0,0 +1,198 @@
+use core::ffi::c_void;
+use std::io;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::ptr::NonNull;
+use std::slice;
+use std::time::Duration;
+
+use crate::libbpf_sys;
+use crate::util;
+use crate::Error;
+use crate::Map;
+use crate::MapType;
+use crate::Result;
+
+/// The canonical interface for producing samples into a `user_ringbuf` map.
+///
+/// `user_ringbuf`s are the counterpart to [`RingBuffer`][crate::RingBuffer]:
+/// userspace reserves space, writes into it, and submits it, while a BPF
+/// program drains the buffer via `bpf_user_ringbuf_drain()`. Because the
+/// kernel only supports a single producer per buffer, `UserRingBuffer` is
+/// intentionally not [`Sync`]; share it across producer threads behind your
+/// own synchronization if you need to.
+#[derive(Debug)]
+pub struct UserRingBuffer {
+    ptr: NonNull<libbpf_sys::user_ring_buffer>,
+}
+
+impl UserRingBuffer {
+    /// Create a new `UserRingBuffer` from the given `map`, which must be of
+    /// type [`MapType::UserRingBuf`].
+    pub fn new(map: &Map) -> Result<Self> {
+        if map.map_type() != MapType::UserRingBuf {
+            return Err(Error::InvalidInput("Must use a UserRingBuf map".into()));
+        }
+
+        let ptr = util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::user_ring_buffer__new(map.fd(), std::ptr::null())
+        })?;
+
+        Ok(Self { ptr })
+    }
+
+    /// Reserve `size` bytes in the ring buffer, returning a guard that
+    /// derefs to the reserved region. The reservation is rounded up to an
+    /// 8-byte alignment by libbpf.
+    ///
+    /// The sample must eventually be handed to [`submit`][Self::submit] or
+    /// [`discard`][Self::discard]; if the returned guard is simply dropped,
+    /// it is discarded automatically.
+    ///
+    /// Returns [`Error::System`] (`ENOSPC`) if the buffer doesn't currently
+    /// have enough free space for `size` bytes.
+    pub fn reserve(&self, size: u32) -> Result<UserRingBufferSample<'_>> {
+        let sample = unsafe { libbpf_sys::user_ring_buffer__reserve(self.ptr.as_ptr(), size) };
+        let sample = NonNull::new(sample)
+            .ok_or_else(|| Error::System(io::Error::last_os_error().raw_os_error().unwrap_or(0)))?;
+
+        Ok(self.sample_from_ptr(sample, size))
+    }
+
+    /// Like [`reserve`][Self::reserve], but blocks until either `size` bytes
+    /// become available or `timeout` elapses. If `timeout` is
+    /// `Duration::MAX`, this will block indefinitely until space is
+    /// available.
+    pub fn reserve_blocking(
+        &self,
+        size: u32,
+        timeout: Duration,
+    ) -> Result<UserRingBufferSample<'_>> {
+        let mut timeout_ms = -1;
+        if timeout != Duration::MAX {
+            timeout_ms = timeout.as_millis() as i32;
+        }
+
+        let sample = unsafe {
+            libbpf_sys::user_ring_buffer__reserve_blocking(self.ptr.as_ptr(), size, timeout_ms)
+        };
+        let sample = NonNull::new(sample)
+            .ok_or_else(|| Error::System(io::Error::last_os_error().raw_os_error().unwrap_or(0)))?;
+
+        Ok(self.sample_from_ptr(sample, size))
+    }
+
+    fn sample_from_ptr(&self, sample: NonNull<c_void>, size: u32) -> UserRingBufferSample<'_> {
+        UserRingBufferSample {
+            rb: self.ptr,
+            sample,
+            size: size as usize,
+            handled: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+// SAFETY: `user_ring_buffer` objects can safely be handed off to another
+// thread, as long as only one thread produces into it at a time.
+unsafe impl Send for UserRingBuffer {}
+
+impl Drop for UserRingBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            libbpf_sys::user_ring_buffer__free(self.ptr.as_ptr());
+        }
+    }
+}
+
+/// A reserved, not-yet-submitted region of a [`UserRingBuffer`].
+///
+/// Dereferences to the raw bytes of the reservation. Dropping the sample
+/// without calling [`submit`][Self::submit] discards it, returning the space
+/// to the ring buffer.
+#[derive(Debug)]
+pub struct UserRingBufferSample<'a> {
+    rb: NonNull<libbpf_sys::user_ring_buffer>,
+    sample: NonNull<c_void>,
+    size: usize,
+    handled: bool,
+    _marker: PhantomData<&'a UserRingBuffer>,
+}
+
+impl UserRingBufferSample<'_> {
+    /// Commit this sample to the ring buffer, making it visible to the BPF
+    /// consumer.
+    pub fn submit(mut self) {
+        unsafe { libbpf_sys::user_ring_buffer__submit(self.rb.as_ptr(), self.sample.as_ptr()) };
+        self.handled = true;
+    }
+
+    /// Abandon this sample, returning its space to the ring buffer without
+    /// making it visible to the BPF consumer.
+    pub fn discard(mut self) {
+        unsafe { libbpf_sys::user_ring_buffer__discard(self.rb.as_ptr(), self.sample.as_ptr()) };
+        self.handled = true;
+    }
+}
+
+impl Deref for UserRingBufferSample<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.sample.as_ptr() as *const u8, self.size) }
+    }
+}
+
+impl DerefMut for UserRingBufferSample<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.sample.as_ptr() as *mut u8, self.size) }
+    }
+}
+
+impl Drop for UserRingBufferSample<'_> {
+    fn drop(&mut self) {
+        if !self.handled {
+            unsafe {
+                libbpf_sys::user_ring_buffer__discard(self.rb.as_ptr(), self.sample.as_ptr());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Check that `UserRingBuffer` is `Send`.
+    #[test]
+    fn user_ringbuffer_is_send() {
+        fn test<T>()
+        where
+            T: Send,
+        {
+        }
+
+        test::<UserRingBuffer>();
+    }
+
+    /// Check that `UserRingBuffer` is *not* `Sync`, as it must not be shared
+    /// across producer threads without external synchronization.
+    #[test]
+    fn user_ringbuffer_is_not_sync() {
+        // The classic ambiguous-impl trick: if `UserRingBuffer` were `Sync`,
+        // both impls below would apply to it and the call at the bottom
+        // would fail to compile with an ambiguity error.
+        trait AmbiguousIfSync<A> {
+            fn some_item() {}
+        }
+
+        impl<T: ?Sized> AmbiguousIfSync<()> for T {}
+
+        struct Invalid;
+
+        impl<T: ?Sized + Sync> AmbiguousIfSync<Invalid> for T {}
+
+        <UserRingBuffer as AmbiguousIfSync<_>>::some_item();
+    }
+}